@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+/// A named group of Unicode code point ranges backing one `@font-face`
+/// `unicode-range` rule, so a browser only downloads the buckets a page
+/// actually needs glyphs from.
+pub struct Bucket {
+    pub name: &'static str,
+    pub ranges: &'static [(u32, u32)],
+}
+
+pub const BUCKETS: &[Bucket] = &[
+    Bucket {
+        name: "latin",
+        ranges: &[(0x0000, 0x00FF)],
+    },
+    Bucket {
+        name: "latin-ext",
+        ranges: &[(0x0100, 0x017F), (0x0180, 0x024F)],
+    },
+    Bucket {
+        name: "vietnamese",
+        ranges: &[(0x1EA0, 0x1EF9)],
+    },
+    Bucket {
+        name: "greek",
+        ranges: &[(0x0370, 0x03FF), (0x1F00, 0x1FFF)],
+    },
+    Bucket {
+        name: "cyrillic",
+        ranges: &[(0x0400, 0x04FF), (0x0500, 0x052F)],
+    },
+    Bucket {
+        name: "hebrew",
+        ranges: &[(0x0590, 0x05FF)],
+    },
+    Bucket {
+        name: "arabic",
+        ranges: &[(0x0600, 0x06FF)],
+    },
+    Bucket {
+        name: "devanagari",
+        ranges: &[(0x0900, 0x097F)],
+    },
+    Bucket {
+        name: "thai",
+        ranges: &[(0x0E00, 0x0E7F)],
+    },
+    Bucket {
+        name: "cjk-symbols",
+        ranges: &[(0x3000, 0x303F)],
+    },
+    Bucket {
+        name: "hiragana-katakana",
+        ranges: &[(0x3040, 0x30FF)],
+    },
+    Bucket {
+        name: "hangul",
+        ranges: &[(0xAC00, 0xD7A3)],
+    },
+    Bucket {
+        name: "cjk-ideographs",
+        ranges: &[(0x3400, 0x4DBF), (0x4E00, 0x9FFF)],
+    },
+];
+
+/// Catch-all bucket name for code points that don't fall in any [`BUCKETS`]
+/// entry. Every collected character ends up in exactly one bucket - nothing
+/// is silently dropped from the subset.
+const OTHER_BUCKET: &str = "other";
+
+/// Returns whether `file_stem` (a filename with its final extension already
+/// stripped) ends in `.{bucket}` for one of [`BUCKETS`] or [`OTHER_BUCKET`] -
+/// i.e. it's this tool's own `{base}.{bucket}.woff2` output naming, not a
+/// base font name. Lets callers that rescan a font directory tell a
+/// previous run's subsets apart from the originals they were cut from.
+pub fn is_bucket_suffixed(file_stem: &str) -> bool {
+    file_stem.rsplit_once('.').is_some_and(|(_, suffix)| {
+        suffix == OTHER_BUCKET || BUCKETS.iter().any(|b| b.name == suffix)
+    })
+}
+
+/// Splits `chars` into script buckets, returning only the non-empty ones in
+/// [`BUCKETS`] order with `other` last.
+pub fn partition_chars(chars: &HashSet<char>) -> Vec<(String, HashSet<char>)> {
+    let mut buckets: Vec<(String, HashSet<char>)> = BUCKETS
+        .iter()
+        .map(|b| (b.name.to_string(), HashSet::new()))
+        .collect();
+    let mut other: HashSet<char> = HashSet::new();
+
+    for &c in chars {
+        let cp = c as u32;
+        match BUCKETS
+            .iter()
+            .position(|b| b.ranges.iter().any(|&(lo, hi)| cp >= lo && cp <= hi))
+        {
+            Some(idx) => {
+                buckets[idx].1.insert(c);
+            }
+            None => {
+                other.insert(c);
+            }
+        }
+    }
+
+    if !other.is_empty() {
+        buckets.push((OTHER_BUCKET.to_string(), other));
+    }
+
+    buckets.retain(|(_, set)| !set.is_empty());
+    buckets
+}
+
+/// Merges a character set into sorted, contiguous `(start, end)` code point
+/// ranges, suitable for a CSS `unicode-range` descriptor.
+pub fn contiguous_ranges(chars: &HashSet<char>) -> Vec<(u32, u32)> {
+    let mut points: Vec<u32> = chars.iter().map(|&c| c as u32).collect();
+    points.sort_unstable();
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for cp in points {
+        match ranges.last_mut() {
+            Some((_, hi)) if cp == *hi + 1 => *hi = cp,
+            _ => ranges.push((cp, cp)),
+        }
+    }
+    ranges
+}
+
+/// Formats ranges as a `unicode-range` descriptor value, e.g.
+/// `U+0000-00FF, U+0131`.
+pub fn format_unicode_range(ranges: &[(u32, u32)]) -> String {
+    ranges
+        .iter()
+        .map(|&(lo, hi)| {
+            if lo == hi {
+                format!("U+{lo:04X}")
+            } else {
+                format!("U+{lo:04X}-{hi:04X}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,96 @@
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+pub const DEFAULT_EXTENSIONS: &[&str] = &[
+    "astro", "md", "mdx", "ts", "tsx", "js", "jsx", "json", "html", "vue", "svelte", "css",
+];
+
+/// Subset project fonts down to the characters actually used in source.
+#[derive(Parser, Debug)]
+#[command(name = "subfont", version, about)]
+pub struct Cli {
+    /// Source directories to scan for used characters (default: `src`)
+    #[arg(value_name = "SRC_DIR")]
+    pub src_dirs: Vec<PathBuf>,
+
+    /// Directory containing the fonts to subset (default: `src/assets/fonts`)
+    #[arg(long, value_name = "DIR")]
+    pub font_dir: Option<PathBuf>,
+
+    /// Directory to write the cache, the manifest and the CSS into (default: `.subfont`);
+    /// the subsets themselves are written into the font directory
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Override the default scanned source file extensions (e.g. --ext astro --ext md)
+    #[arg(long = "ext", value_name = "EXT")]
+    pub extensions: Vec<String>,
+
+    /// Force these literal characters into every generated subset
+    #[arg(long, value_name = "CHARS")]
+    pub include_chars: Option<String>,
+
+    /// Force a Unicode range (e.g. U+0000-00FF) into every generated subset
+    #[arg(long = "include-range", value_name = "U+XXXX-YYYY")]
+    pub include_ranges: Vec<String>,
+
+    /// Report the planned subsets and size estimates without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Font family to fall back to, in order, for characters no other
+    /// discovered font has a glyph for (repeatable, e.g. --fallback-family "Noto Sans")
+    #[arg(long = "fallback-family", value_name = "FAMILY")]
+    pub fallback_families: Vec<String>,
+}
+
+impl Cli {
+    /// The scanned source extensions, normalized to a leading dot.
+    pub fn extensions(&self) -> Vec<String> {
+        if self.extensions.is_empty() {
+            DEFAULT_EXTENSIONS.iter().map(|e| format!(".{e}")).collect()
+        } else {
+            self.extensions
+                .iter()
+                .map(|e| format!(".{}", e.trim_start_matches('.')))
+                .collect()
+        }
+    }
+
+    /// Characters forced into every subset via `--include-chars`/`--include-range`.
+    pub fn forced_chars(&self) -> Result<HashSet<char>> {
+        let mut chars = HashSet::new();
+        if let Some(literal) = &self.include_chars {
+            chars.extend(literal.chars());
+        }
+        for range in &self.include_ranges {
+            let (lo, hi) = parse_unicode_range(range)
+                .with_context(|| format!("invalid --include-range {range:?}"))?;
+            for cp in lo..=hi {
+                if let Some(c) = char::from_u32(cp) {
+                    chars.insert(c);
+                }
+            }
+        }
+        Ok(chars)
+    }
+}
+
+/// Parses `U+XXXX` or `U+XXXX-YYYY` (the `U+` prefix is optional) into an
+/// inclusive code point range.
+fn parse_unicode_range(s: &str) -> Result<(u32, u32)> {
+    let s = s.trim();
+    let s = s.strip_prefix("U+").or_else(|| s.strip_prefix("u+")).unwrap_or(s);
+    let (lo_str, hi_str) = s.split_once('-').unwrap_or((s, s));
+    let lo = u32::from_str_radix(lo_str, 16)
+        .with_context(|| format!("invalid code point {lo_str:?}"))?;
+    let hi = u32::from_str_radix(hi_str, 16)
+        .with_context(|| format!("invalid code point {hi_str:?}"))?;
+    ensure!(
+        lo <= hi,
+        "range start {lo:#06X} is greater than end {hi:#06X}"
+    );
+    Ok((lo, hi))
+}
@@ -0,0 +1,61 @@
+use fontcull::{decompress_font, FontFormat};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use ttf_parser::Face;
+
+/// Returns the subset of `chars` that `path` has a glyph for. `None` on
+/// read/parse failure; callers then treat the font as having no coverage
+/// data and fall back to the full requested character set.
+pub fn font_coverage(path: &Path, chars: &HashSet<char>) -> Option<HashSet<char>> {
+    let bytes = fs::read(path).ok()?;
+    let data = if FontFormat::detect(&bytes) == FontFormat::Woff2 {
+        decompress_font(&bytes).ok()?
+    } else {
+        bytes
+    };
+    let face = Face::parse(&data, 0).ok()?;
+    Some(
+        chars
+            .iter()
+            .copied()
+            .filter(|&c| face.glyph_index(c).is_some())
+            .collect(),
+    )
+}
+
+/// Walks `fallback_chain` (ordered family names) for every character in
+/// `uncovered`, assigning it to the first chain entry whose base font covers
+/// it per `coverage`. Returns the overflow characters to fold into each
+/// base's own subset, keyed by base name.
+///
+/// `uncovered` must exclude coverage contributed by the chain's own fonts -
+/// callers that union every discovered font's coverage (chain members
+/// included) into `uncovered` will find this never assigns anything, since a
+/// chain font covering a character would already have removed it from
+/// `uncovered`.
+pub fn assign_fallbacks(
+    uncovered: &HashSet<char>,
+    coverage: &HashMap<String, HashSet<char>>,
+    base_by_family: &HashMap<String, String>,
+    fallback_chain: &[String],
+) -> HashMap<String, HashSet<char>> {
+    let mut overflow: HashMap<String, HashSet<char>> = HashMap::new();
+    if fallback_chain.is_empty() {
+        return overflow;
+    }
+
+    for &c in uncovered {
+        for family in fallback_chain {
+            let Some(base) = base_by_family.get(family) else {
+                continue;
+            };
+            if coverage.get(base).is_some_and(|cov| cov.contains(&c)) {
+                overflow.entry(base.clone()).or_default().insert(c);
+                break;
+            }
+        }
+    }
+
+    overflow
+}
@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+/// Scans `text` for HTML numeric entities (`&#xf001;`, `&#61441;`) and JS
+/// unicode escapes (four hex digits after a backslash-u, or braced
+/// `\u{f001}`), decoding each to the character it represents. A plain
+/// `text.chars()` pass never sees codepoints referenced only this way.
+pub fn extract_escaped_chars(text: &str) -> HashSet<char> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut found = HashSet::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1) == Some(&'u') => {
+                i = scan_js_escape(&chars, i, &mut found);
+            }
+            '&' if chars.get(i + 1) == Some(&'#') => {
+                i = scan_html_entity(&chars, i, &mut found);
+            }
+            _ => i += 1,
+        }
+    }
+
+    found
+}
+
+/// Scans `text` for CSS string escapes (`\f001`, `\00f001`), decoding each
+/// to the character it represents.
+///
+/// Unlike [`extract_escaped_chars`], this only looks for a bare backslash
+/// followed by 1-6 hex digits - the same shape as common regex
+/// metacharacters (`\d`, `\b`, `\D`, `\B` are all valid hex runs), so this
+/// must only run over actual CSS content, never over `.js`/`.ts` source.
+pub fn extract_css_escapes(text: &str) -> HashSet<char> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut found = HashSet::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '\\' {
+            i = scan_css_escape(&chars, i, &mut found);
+        } else {
+            i += 1;
+        }
+    }
+
+    found
+}
+
+/// Parses a JS `\uf001` or `\u{f001}` escape starting at the `\`, returning
+/// the index to resume scanning from.
+fn scan_js_escape(chars: &[char], start: usize, found: &mut HashSet<char>) -> usize {
+    let after_u = start + 2;
+    if chars.get(after_u) == Some(&'{') {
+        let digits_start = after_u + 1;
+        let end = hex_digit_run(chars, digits_start, usize::MAX);
+        if end > digits_start && chars.get(end) == Some(&'}') {
+            insert_hex(chars, digits_start, end, found);
+            return end + 1;
+        }
+    } else {
+        let end = hex_digit_run(chars, after_u, 4);
+        if end == after_u + 4 {
+            insert_hex(chars, after_u, end, found);
+            return end;
+        }
+    }
+    start + 1
+}
+
+/// Parses a CSS escape (1-6 hex digits, optionally followed by one
+/// whitespace character that terminates it per the CSS spec) starting at the
+/// `\`, returning the index to resume scanning from.
+fn scan_css_escape(chars: &[char], start: usize, found: &mut HashSet<char>) -> usize {
+    let digits_start = start + 1;
+    let end = hex_digit_run(chars, digits_start, 6);
+    if end == digits_start {
+        return start + 1;
+    }
+    insert_hex(chars, digits_start, end, found);
+    if chars.get(end).is_some_and(|c| c.is_whitespace()) {
+        end + 1
+    } else {
+        end
+    }
+}
+
+/// Parses an HTML numeric entity (`&#xf001;` or `&#61441;`) starting at the
+/// `&`, returning the index to resume scanning from.
+fn scan_html_entity(chars: &[char], start: usize, found: &mut HashSet<char>) -> usize {
+    let mut pos = start + 2;
+    let hex = matches!(chars.get(pos), Some('x' | 'X'));
+    if hex {
+        pos += 1;
+    }
+    let digits_start = pos;
+    let end = if hex {
+        hex_digit_run(chars, digits_start, usize::MAX)
+    } else {
+        let mut e = digits_start;
+        while chars.get(e).is_some_and(|c| c.is_ascii_digit()) {
+            e += 1;
+        }
+        e
+    };
+    if end > digits_start && chars.get(end) == Some(&';') {
+        let digits: String = chars[digits_start..end].iter().collect();
+        let radix = if hex { 16 } else { 10 };
+        if let Ok(cp) = u32::from_str_radix(&digits, radix) {
+            if let Some(c) = char::from_u32(cp) {
+                found.insert(c);
+            }
+        }
+        end + 1
+    } else {
+        start + 1
+    }
+}
+
+/// Returns the end index of a run of ASCII hex digits starting at `from`,
+/// capped at `max_len` digits.
+fn hex_digit_run(chars: &[char], from: usize, max_len: usize) -> usize {
+    let mut end = from;
+    while end - from < max_len && chars.get(end).is_some_and(|c| c.is_ascii_hexdigit()) {
+        end += 1;
+    }
+    end
+}
+
+fn insert_hex(chars: &[char], from: usize, to: usize, found: &mut HashSet<char>) {
+    let digits: String = chars[from..to].iter().collect();
+    if let Ok(cp) = u32::from_str_radix(&digits, 16) {
+        if let Some(c) = char::from_u32(cp) {
+            found.insert(c);
+        }
+    }
+}
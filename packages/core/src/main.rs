@@ -1,11 +1,18 @@
+mod buckets;
+mod cli;
+mod coverage;
+mod escapes;
+mod macroman;
+
 use anyhow::{Context, Result};
+use clap::Parser;
+use cli::Cli;
 use fontcull::{decompress_font, subset_font_to_woff2, FontFormat};
 use md5;
 use rayon::prelude::*;
 use serde_json;
 use std::collections::BTreeMap;
 use std::collections::{HashMap, HashSet};
-use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use ttf_parser::name_id;
@@ -30,7 +37,33 @@ struct Colors {
     end: &'static str,
 }
 
-const CULL_VERSION: &str = "fontcull-2";
+const CULL_VERSION: &str = "fontcull-3";
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+struct SubsetEntry {
+    bucket: String,
+    file: String,
+    ranges: Vec<(u32, u32)>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+struct FontCacheEntry {
+    input_hash: String,
+    subsets: Vec<SubsetEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+struct ManifestEntry {
+    family: String,
+    style: String,
+    weight: u16,
+    width: u16,
+    slant: String,
+    variable: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    variable_axes: Vec<VariableAxis>,
+    subsets: Vec<SubsetEntry>,
+}
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 struct Cache {
@@ -39,7 +72,7 @@ struct Cache {
     #[serde(default = "current_version")]
     version: String,
     #[serde(default)]
-    fonts: HashMap<String, String>,
+    fonts: HashMap<String, FontCacheEntry>,
 }
 
 fn current_version() -> String {
@@ -58,21 +91,39 @@ fn run_subset() -> Result<()> {
         C.bold, C.end
     );
 
-    let project_root = env::var("PROJECT_ROOT")
-        .ok()
-        .and_then(|p| fs::canonicalize(p).ok())
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
+    let cli = Cli::parse();
 
-    let src_dir = project_root.join("src");
-    let font_dir = src_dir.join("assets/fonts");
-    let subfont_dir = project_root.join(".subfont");
+    let src_dirs: Vec<PathBuf> = if cli.src_dirs.is_empty() {
+        vec![PathBuf::from("src")]
+    } else {
+        cli.src_dirs.clone()
+    };
+    let font_dir = cli
+        .font_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("src/assets/fonts"));
+    let subfont_dir = cli
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(".subfont"));
     let source_dir = subfont_dir.join("source");
     let cache_file = subfont_dir.join("cache.json");
     let manifest_path = subfont_dir.join("font-manifest.json");
 
-    let (text, text_hash) = get_unique_chars(&src_dir)?;
+    let extensions = cli.extensions();
+    let forced_chars = cli.forced_chars()?;
+
+    let (text, text_hash) = get_unique_chars(&src_dirs, &extensions, &forced_chars)?;
     let chars: HashSet<char> = text.chars().collect();
 
+    let fallback_chain = cli.fallback_families.clone();
+    let text_hash = {
+        let mut context = md5::Context::new();
+        context.consume(text_hash.as_bytes());
+        context.consume(fallback_chain.join(",").as_bytes());
+        format!("{:x}", context.compute())
+    };
+
     if chars.len() > 10000 {
         println!(
             " {}Large character set detected ({} unique chars) - subsetting may take a while{}",
@@ -87,6 +138,10 @@ fn run_subset() -> Result<()> {
         return Ok(());
     }
 
+    if cli.dry_run {
+        return dry_run_report(&font_dir, &chars);
+    }
+
     fs::create_dir_all(&subfont_dir)?;
     fs::create_dir_all(&source_dir)?;
 
@@ -154,8 +209,7 @@ fn run_subset() -> Result<()> {
         }
     }
 
-    let mut groups: HashMap<String, Vec<(usize, String, PathBuf, bool, String, String)>> =
-        HashMap::new();
+    let mut groups: HashMap<String, Vec<(usize, String, PathBuf, FontMeta)>> = HashMap::new();
     let mut seen_realpaths: HashSet<PathBuf> = HashSet::new();
 
     for entry in fs::read_dir(&source_dir)? {
@@ -188,26 +242,26 @@ fn run_subset() -> Result<()> {
             println!(" {}Skipping symlink {}{}", C.yellow, file_name, C.end);
             continue;
         }
-        let key_info = match get_font_key(&path) {
-            Some(info) => info,
+        if lower_ext.as_deref() == Some(".woff2")
+            && buckets::is_bucket_suffixed(&path.file_stem().unwrap().to_string_lossy())
+        {
+            continue;
+        }
+        let meta = match get_font_key(&path) {
+            Some(meta) => meta,
             None => continue,
         };
-        let (_key, is_variable, family, display_style) = key_info;
         let base_name = path.file_stem().unwrap().to_string_lossy().to_string();
         let pri = match lower_ext.as_deref() {
             Some(".woff2") => 0,
             Some(".ttf") => 2,
             Some(".otf") => 3,
             _ => 10,
-        } + if is_variable { 10 } else { 0 };
-        groups.entry(base_name).or_default().push((
-            pri,
-            file_name,
-            path,
-            is_variable,
-            family,
-            display_style,
-        ));
+        } + if meta.is_variable { 10 } else { 0 };
+        groups
+            .entry(base_name)
+            .or_default()
+            .push((pri, file_name, path, meta));
     }
 
     let mut new_cache = Cache {
@@ -215,158 +269,370 @@ fn run_subset() -> Result<()> {
         version: CULL_VERSION.to_string(),
         fonts: HashMap::new(),
     };
-    let mut manifest_mapping: HashMap<String, String> = HashMap::new();
+    let mut manifest_mapping: HashMap<String, ManifestEntry> = HashMap::new();
+    let mut css_rules: Vec<(FontMeta, SubsetEntry)> = Vec::new();
+
+    if chars.is_empty() {
+        println!(
+            " {}No characters collected - nothing to subset{}",
+            C.yellow, C.end
+        );
+    }
 
-    type Task = (String, PathBuf, String, String, PathBuf, PathBuf, PathBuf, String); 
+    // Glyph-coverage pass: find out which discovered font actually has a
+    // glyph for each requested character, so gaps are reported instead of
+    // silently subsetting to `.notdef`.
+    let mut coverage: HashMap<String, HashSet<char>> = HashMap::new();
+    let mut base_by_family: HashMap<String, String> = HashMap::new();
+    // Iterate in sorted order so which base_name wins a shared family in
+    // `base_by_family` (and therefore which physical file receives fallback
+    // overflow via `assign_fallbacks`) doesn't depend on HashMap iteration
+    // order, which would make output non-reproducible across runs.
+    let mut base_names: Vec<&String> = groups.keys().collect();
+    base_names.sort();
+    for base_name in base_names {
+        let candidates = &groups[base_name];
+        let mut sorted = candidates.clone();
+        sorted.sort_by_key(|c| c.0);
+        let (_pri, _filename, input_path, meta) = &sorted[0];
+        if let Some(cov) = coverage::font_coverage(input_path, &chars) {
+            let missing = chars.len() - cov.len();
+            if missing > 0 {
+                println!(
+                    " {}- {}: {}missing {} of {} requested characters{}",
+                    C.blue, base_name, C.yellow, missing, chars.len(), C.end
+                );
+            }
+            coverage.insert(base_name.clone(), cov);
+        }
+        base_by_family
+            .entry(meta.family.clone())
+            .or_insert_with(|| base_name.clone());
+    }
+
+    // Fonts named in the fallback chain exist to pick up slack for other
+    // fonts, so their own coverage doesn't count toward "already covered" -
+    // otherwise a chain font's coverage would always already be folded into
+    // `covered_anywhere` and no character could ever reach `assign_fallbacks`.
+    let fallback_bases: HashSet<String> = fallback_chain
+        .iter()
+        .filter_map(|family| base_by_family.get(family).cloned())
+        .collect();
+    let covered_anywhere: HashSet<char> = coverage
+        .iter()
+        .filter(|(base, _)| !fallback_bases.contains(*base))
+        .flat_map(|(_, cov)| cov.iter().copied())
+        .collect();
+    let uncovered: HashSet<char> = chars.difference(&covered_anywhere).copied().collect();
+    let unrendered: HashSet<char> = uncovered
+        .iter()
+        .copied()
+        .filter(|c| !coverage.values().any(|cov| cov.contains(c)))
+        .collect();
+    if !unrendered.is_empty() {
+        let mut preview: Vec<char> = unrendered.iter().copied().take(20).collect();
+        preview.sort_unstable();
+        let preview_str: String = preview.iter().collect();
+        println!(
+            " {}⚠ {} character(s) are not covered by any discovered font and will render as .notdef: {}{}{}",
+            C.red, unrendered.len(), preview_str,
+            if unrendered.len() > preview.len() { "…" } else { "" },
+            C.end
+        );
+    }
+
+    let overflow_by_base =
+        coverage::assign_fallbacks(&uncovered, &coverage, &base_by_family, &fallback_chain);
+    if !fallback_chain.is_empty() {
+        let recovered: usize = overflow_by_base.values().map(|s| s.len()).sum();
+        if recovered > 0 {
+            println!(
+                " {}Fallback chain recovered {} character(s) into the chain's fonts{}",
+                C.green, recovered, C.end
+            );
+        }
+    }
+
+    type Task = (
+        String,       // base_name
+        FontMeta,     // metadata
+        PathBuf,      // input_path
+        String,       // input_hash
+        String,       // bucket
+        HashSet<char>,// bucket chars
+        Vec<(u32, u32)>, // bucket ranges
+        String,       // output_name
+        PathBuf,      // output_path
+        PathBuf,      // temp_path
+        String,       // manifest_alias
+    );
     let mut to_process: Vec<Task> = Vec::new();
 
     for (base_name, mut candidates) in groups {
         candidates.sort_by_key(|c| c.0);
-        let (_pri, _filename, input_path, _is_var, _family, _display_style) = candidates[0].clone();
+        let (_pri, _filename, input_path, meta) = candidates[0].clone();
 
         let input_hash = get_file_hash(&input_path);
         if input_hash == "hash_error" {
             continue;
         }
 
-        let output_base = base_name.clone();
-        let output_name = format!("{output_base}.woff2");
-        let output_path = font_dir.join(&output_name);
-        let temp_path = font_dir.join(format!("{output_name}.tmp"));
         let manifest_alias = base_name.to_lowercase().replace(' ', "");
 
-        let cached_entry = cache.fonts.get(&output_base);
-        if cached_entry.map(|s| s.as_str()) == Some(&input_hash)
-            && cache.text_hash == new_cache.text_hash
-            && output_path.is_file()
-        {
-            let size_kb = fs::metadata(&output_path)?.len() / 1024;
-            println!(
-                " {}- {}: {}Up to date ({}KB cached){}",
-                C.blue, output_name, C.green, size_kb, C.end
-            );
-            new_cache.fonts.insert(output_base, input_hash);
-            manifest_mapping.insert(manifest_alias, output_name);
-            continue;
+        // Bucket only the characters this specific font covers, plus
+        // whatever overflow `assign_fallbacks` routed to it - fonts with no
+        // coverage entry (parse failed) fall back to the full `chars` set.
+        let mut effective_chars = coverage.get(&base_name).cloned().unwrap_or_else(|| chars.clone());
+        if let Some(overflow) = overflow_by_base.get(&base_name) {
+            effective_chars.extend(overflow.iter().copied());
+        }
+        let expected_buckets: Vec<(String, HashSet<char>, Vec<(u32, u32)>)> =
+            buckets::partition_chars(&effective_chars)
+                .into_iter()
+                .map(|(bucket, set)| {
+                    let ranges = buckets::contiguous_ranges(&set);
+                    (bucket, set, ranges)
+                })
+                .collect();
+
+        // Remove stale outputs from a previous run's bucket set (or the old
+        // single-file `base.woff2` layout) so the font directory never ships
+        // subsets that no longer correspond to the current character set.
+        let expected_names: HashSet<String> = expected_buckets
+            .iter()
+            .map(|(bucket, _, _)| format!("{base_name}.{bucket}.woff2"))
+            .collect();
+        if let Ok(entries) = fs::read_dir(&font_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                let is_ours = name.ends_with(".woff2")
+                    && (name == format!("{base_name}.woff2")
+                        || path
+                            .file_stem()
+                            .and_then(|s| s.to_str())
+                            .is_some_and(|stem| {
+                                buckets::is_bucket_suffixed(stem)
+                                    && stem
+                                        .rsplit_once('.')
+                                        .is_some_and(|(prefix, _)| prefix == base_name)
+                            }));
+                if is_ours && !expected_names.contains(&name) {
+                    let _ = fs::remove_file(&path);
+                }
+            }
         }
 
-        to_process.push((
-            base_name,
-            input_path,
-            input_hash,
-            output_name,
-            output_path,
-            temp_path,
-            font_dir.clone(),
-            manifest_alias,
-        ));
+        let cached_entry = cache.fonts.get(&base_name);
+        let all_cached = cached_entry.is_some_and(|entry| {
+            entry.input_hash == input_hash
+                && cache.text_hash == new_cache.text_hash
+                && entry.subsets.len() == expected_buckets.len()
+                && entry
+                    .subsets
+                    .iter()
+                    .all(|s| font_dir.join(&s.file).is_file())
+        });
+
+        if let Some(entry) = cached_entry {
+            if all_cached {
+                let total_kb: u64 = entry
+                    .subsets
+                    .iter()
+                    .map(|s| fs::metadata(font_dir.join(&s.file)).map(|m| m.len()).unwrap_or(0))
+                    .sum::<u64>()
+                    / 1024;
+                println!(
+                    " {}- {}: {}{} subsets up to date ({}KB cached){}",
+                    C.blue,
+                    base_name,
+                    C.green,
+                    entry.subsets.len(),
+                    total_kb,
+                    C.end
+                );
+                new_cache.fonts.insert(base_name.clone(), entry.clone());
+                manifest_mapping.insert(
+                    manifest_alias.clone(),
+                    ManifestEntry {
+                        family: meta.family.clone(),
+                        style: meta.style.clone(),
+                        weight: meta.weight,
+                        width: meta.width,
+                        slant: meta.slant.to_string(),
+                        variable: meta.is_variable,
+                        variable_axes: meta.variable_axes.clone(),
+                        subsets: entry.subsets.clone(),
+                    },
+                );
+                for subset in &entry.subsets {
+                    css_rules.push((meta.clone(), subset.clone()));
+                }
+                continue;
+            }
+        }
+
+        for (bucket, bucket_chars, ranges) in &expected_buckets {
+            let output_name = format!("{base_name}.{bucket}.woff2");
+            let output_path = font_dir.join(&output_name);
+            let temp_path = font_dir.join(format!("{output_name}.tmp"));
+            to_process.push((
+                base_name.clone(),
+                meta.clone(),
+                input_path.clone(),
+                input_hash.clone(),
+                bucket.clone(),
+                bucket_chars.clone(),
+                ranges.clone(),
+                output_name,
+                output_path,
+                temp_path,
+                manifest_alias.clone(),
+            ));
+        }
     }
 
-    let results: Vec<(String, String, String, String, usize, usize)> = to_process.into_par_iter().filter_map(
-        |(base_name, input_path, input_hash, output_name, output_path, temp_path, font_dir, manifest_alias)| {
-            let input_bytes = match fs::read(&input_path) {
-                Ok(b) => b,
-                Err(e) => {
-                    println!(" {}Failed to read input for {}: {}{}", C.red, output_name, e, C.end);
-                    return None;
-                }
-            };
-
-            let orig_size = input_bytes.len() / 1024;
-
-            let font_data = if input_path
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| e.to_lowercase())
-                == Some("woff2".to_string())
-            {
-                match decompress_font(&input_bytes) {
-                    Ok(d) => d,
+    let font_features: [[u8; 4]; 6] = [*b"ccmp", *b"locl", *b"kern", *b"liga", *b"mark", *b"mkmk"];
+
+    let results: Vec<(String, FontMeta, String, String, SubsetEntry)> = to_process
+        .into_par_iter()
+        .filter_map(
+            |(base_name, meta, input_path, input_hash, bucket, bucket_chars, ranges, output_name, output_path, temp_path, manifest_alias)| {
+                let input_bytes = match fs::read(&input_path) {
+                    Ok(b) => b,
                     Err(e) => {
-                        println!(" {}Decompress failed for {}: {}{}", C.yellow, output_name, e, C.end);
+                        println!(" {}Failed to read input for {}: {}{}", C.red, output_name, e, C.end);
                         return None;
                     }
-                }
-            } else {
-                input_bytes.clone()
-            };
-
-            let font_features: [[u8; 4]; 6] = [
-    *b"ccmp",  
-    *b"locl",
-    *b"kern",
-    *b"liga",
-    *b"mark",
-    *b"mkmk",
-];
-
-            let woff2_data = match subset_font_to_woff2(&font_data, &chars, &font_features) {
-                Ok(data) => data,
-                Err(e) => {
-                    println!(
-                        " {}Subset error for {}: {}{}",
-                        C.yellow, output_name, e, C.end
-                    );
-                    println!(
-                        " {}✗ Failed to process {} - keeping original formats{}",
-                        C.red, output_name, C.end
-                    );
-                    let _ = fs::remove_file(&temp_path);
-                    return None;
-                }
-            };
-
-            let new_size = woff2_data.len() / 1024;
-
-            if fs::write(&temp_path, &woff2_data).is_err() {
-                return None;
-            }
-            if output_path.is_file() {
-                let _ = fs::remove_file(&output_path);
-            }
-            if fs::rename(&temp_path, &output_path).is_err() {
-                return None;
-            }
+                };
 
-            if let Ok(entries) = fs::read_dir(&font_dir) {
-                for old_entry in entries.flatten() {
-                    let old_path = old_entry.path();
-                    if old_path.is_file() {
-                        let old_name = old_path.file_name().unwrap().to_string_lossy();
-                        if old_name == output_name {
-                            continue;
-                        }
-                        let old_base = old_path.file_stem().unwrap().to_string_lossy().to_string();
-                        if old_base == base_name {
-                            if let Err(e) = fs::remove_file(&old_path) {
-                                println!(
-                                    " {}Could not remove old {}: {}{}",
-                                    C.yellow, old_name, e, C.end
-                                );
-                            }
+                let orig_size = input_bytes.len() / 1024;
+
+                let font_data = if input_path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+                    == Some("woff2".to_string())
+                {
+                    match decompress_font(&input_bytes) {
+                        Ok(d) => d,
+                        Err(e) => {
+                            println!(" {}Decompress failed for {}: {}{}", C.yellow, output_name, e, C.end);
+                            return None;
                         }
                     }
-                }
-            }
+                } else {
+                    input_bytes.clone()
+                };
 
-            println!(
-                " {}{}✓{} {}{}{}: {}KB → {}{}KB{}",
-                C.green, C.bold, C.end, C.bold, output_name, C.end, orig_size, C.green, new_size, C.end
-            );
+                let woff2_data = match subset_font_to_woff2(&font_data, &bucket_chars, &font_features) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        println!(
+                            " {}Subset error for {} ({}): {}{}",
+                            C.yellow, output_name, bucket, e, C.end
+                        );
+                        println!(
+                            " {}✗ Failed to process {} - keeping original formats{}",
+                            C.red, output_name, C.end
+                        );
+                        let _ = fs::remove_file(&temp_path);
+                        return None;
+                    }
+                };
 
-            Some((base_name, input_hash, manifest_alias, output_name, orig_size, new_size))
-        },
-    ).collect();
+                let new_size = woff2_data.len() / 1024;
 
-    for (base_name, input_hash, manifest_alias, output_name, _orig_size, _new_size) in results {
-        new_cache.fonts.insert(base_name, input_hash);
-        manifest_mapping.insert(manifest_alias, output_name);
+                if fs::write(&temp_path, &woff2_data).is_err() {
+                    return None;
+                }
+                if output_path.is_file() {
+                    let _ = fs::remove_file(&output_path);
+                }
+                if fs::rename(&temp_path, &output_path).is_err() {
+                    return None;
+                }
+
+                println!(
+                    " {}{}✓{} {}{}{}: {}KB → {}{}KB{} ({} subset, {})",
+                    C.green, C.bold, C.end, C.bold, output_name, C.end, orig_size, C.green, new_size, C.end,
+                    bucket, buckets::format_unicode_range(&ranges)
+                );
+
+                Some((
+                    base_name,
+                    meta,
+                    input_hash,
+                    manifest_alias,
+                    SubsetEntry {
+                        bucket,
+                        file: output_name,
+                        ranges,
+                    },
+                ))
+            },
+        )
+        .collect();
+
+    let mut per_base_subsets: HashMap<String, Vec<SubsetEntry>> = HashMap::new();
+    let mut per_base_hash: HashMap<String, String> = HashMap::new();
+    let mut per_base_meta: HashMap<String, (FontMeta, String)> = HashMap::new();
+    for (base_name, meta, input_hash, manifest_alias, subset) in results {
+        per_base_hash.insert(base_name.clone(), input_hash);
+        per_base_subsets
+            .entry(base_name.clone())
+            .or_default()
+            .push(subset.clone());
+        css_rules.push((meta.clone(), subset));
+        per_base_meta.insert(base_name, (meta, manifest_alias));
+    }
+    for (base_name, subsets) in per_base_subsets {
+        let input_hash = per_base_hash.remove(&base_name).unwrap_or_default();
+        if let Some((meta, manifest_alias)) = per_base_meta.remove(&base_name) {
+            manifest_mapping.insert(
+                manifest_alias,
+                ManifestEntry {
+                    family: meta.family,
+                    style: meta.style,
+                    weight: meta.weight,
+                    width: meta.width,
+                    slant: meta.slant.to_string(),
+                    variable: meta.is_variable,
+                    variable_axes: meta.variable_axes,
+                    subsets: subsets.clone(),
+                },
+            );
+        }
+        new_cache.fonts.insert(
+            base_name,
+            FontCacheEntry {
+                input_hash,
+                subsets,
+            },
+        );
     }
 
     let sorted_manifest: BTreeMap<_, _> = manifest_mapping.into_iter().collect();
     let manifest_json = serde_json::to_string_pretty(&sorted_manifest)?;
     fs::write(&manifest_path, manifest_json)?;
 
+    let css_path = subfont_dir.join("fonts.css");
+    let mut css = String::new();
+    for (meta, subset) in &css_rules {
+        css.push_str(&font_face_rule(meta, subset));
+    }
+    fs::write(&css_path, css)?;
+    println!(
+        " {}{}✓{} Stylesheet generated at {}",
+        C.green,
+        C.bold,
+        C.end,
+        css_path.file_name().unwrap().to_string_lossy()
+    );
+
     println!(
         " {}{}✓{} Manifest generated at {}",
         C.green,
@@ -386,18 +652,159 @@ fn run_subset() -> Result<()> {
     Ok(())
 }
 
-fn get_unique_chars(src_dir: &Path) -> Result<(String, String)> {
+/// Reports the subsets `--dry-run` would produce - per-font buckets, their
+/// unicode ranges and a rough size estimate - without touching the cache,
+/// manifest, CSS or any woff2 output.
+fn dry_run_report(font_dir: &Path, chars: &HashSet<char>) -> Result<()> {
+    println!(
+        "\n {}Dry run - no files will be written.{}",
+        C.yellow, C.end
+    );
+
+    // Group candidate files by base name exactly like the real run does, so
+    // a font directory that already holds a previous run's `base.bucket.woff2`
+    // outputs (they live alongside the originals, not in a separate
+    // directory) is reported once per typeface instead of once per file.
+    let mut groups: HashMap<String, Vec<(usize, PathBuf)>> = HashMap::new();
+    for entry in fs::read_dir(font_dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let lower_ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        if !matches!(lower_ext.as_deref(), Some("ttf" | "otf" | "woff2")) {
+            continue;
+        }
+        let base_name = path.file_stem().unwrap().to_string_lossy().to_string();
+        if lower_ext.as_deref() == Some("woff2") && buckets::is_bucket_suffixed(&base_name) {
+            continue;
+        }
+        let pri = match lower_ext.as_deref() {
+            Some("woff2") => 0,
+            Some("ttf") => 2,
+            Some("otf") => 3,
+            _ => 10,
+        };
+        groups.entry(base_name).or_default().push((pri, path));
+    }
+
+    let mut total_estimate_kb: u64 = 0;
+    for (base_name, mut candidates) in groups {
+        candidates.sort_by_key(|c| c.0);
+        let (_pri, path) = candidates.remove(0);
+
+        let Some(meta) = get_font_key(&path) else {
+            continue;
+        };
+        let Some(covered) = coverage::font_coverage(&path, chars) else {
+            continue;
+        };
+        if covered.is_empty() {
+            continue;
+        }
+
+        let orig_size_kb = fs::metadata(&path)?.len() / 1024;
+
+        println!(
+            "\n {}{} {} {}{}",
+            C.bold, base_name, meta.style, meta.weight, C.end
+        );
+        for (bucket, bucket_chars) in buckets::partition_chars(&covered) {
+            let ranges = buckets::contiguous_ranges(&bucket_chars);
+            let estimate_kb = ((orig_size_kb as f64)
+                * (bucket_chars.len() as f64 / covered.len().max(1) as f64))
+                .round() as u64;
+            total_estimate_kb += estimate_kb;
+            println!(
+                "   {}- {}.{}.woff2{}: ~{}KB ({} chars, {})",
+                C.blue,
+                base_name,
+                bucket,
+                C.end,
+                estimate_kb,
+                bucket_chars.len(),
+                buckets::format_unicode_range(&ranges)
+            );
+        }
+    }
+
+    println!(
+        "\n {}{}Estimated total: ~{}KB across all planned subsets.{}\n",
+        C.bold, C.green, total_estimate_kb, C.end
+    );
+
+    Ok(())
+}
+
+/// Maps an OS/2 `usWidthClass` (1-9) to the `font-stretch` percentage it
+/// corresponds to, per the OpenType spec table.
+fn width_class_to_percent(width_class: u16) -> f32 {
+    match width_class {
+        1 => 50.0,
+        2 => 62.5,
+        3 => 75.0,
+        4 => 87.5,
+        6 => 112.5,
+        7 => 125.0,
+        8 => 150.0,
+        9 => 200.0,
+        _ => 100.0,
+    }
+}
+
+/// Renders one `@font-face` block for a subset, using the typeface's actual
+/// weight/width/slant (and `fvar` axis ranges for variable fonts) so the
+/// generated stylesheet never needs hand correction to match the subset.
+fn font_face_rule(meta: &FontMeta, subset: &SubsetEntry) -> String {
+    let weight = meta
+        .variable_axes
+        .iter()
+        .find(|a| a.tag == "wght")
+        .map(|a| format!("{} {}", a.min as u16, a.max as u16))
+        .unwrap_or_else(|| meta.weight.to_string());
+
+    let stretch = meta
+        .variable_axes
+        .iter()
+        .find(|a| a.tag == "wdth")
+        .map(|a| format!("{}% {}%", a.min, a.max))
+        .unwrap_or_else(|| format!("{}%", width_class_to_percent(meta.width)));
+
+    format!(
+        "@font-face {{\n  font-family: \"{family}\";\n  font-weight: {weight};\n  font-style: {style};\n  font-stretch: {stretch};\n  src: url(\"{file}\") format(\"woff2\");\n  unicode-range: {range};\n  font-display: swap;\n}}\n\n",
+        family = meta.family,
+        weight = weight,
+        style = meta.slant,
+        stretch = stretch,
+        file = subset.file,
+        range = buckets::format_unicode_range(&subset.ranges),
+    )
+}
+
+fn get_unique_chars(
+    src_dirs: &[PathBuf],
+    extensions: &[String],
+    forced_chars: &HashSet<char>,
+) -> Result<(String, String)> {
     let default_str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
     let mut chars: HashSet<char> = default_str.chars().collect();
+    chars.extend(forced_chars);
 
-    let extensions = [
-        ".astro", ".md", ".mdx", ".ts", ".tsx", ".js", ".jsx", ".json", ".html", ".vue", ".svelte"
-    ];
-    let ext_set: HashSet<&str> = extensions.iter().copied().collect();
+    let ext_set: HashSet<&str> = extensions.iter().map(|e| e.as_str()).collect();
 
     let mut paths: Vec<PathBuf> = Vec::new();
 
-    if src_dir.is_dir() {
+    for src_dir in src_dirs {
+        if !src_dir.is_dir() {
+            continue;
+        }
         for entry in WalkDir::new(src_dir).into_iter().filter_map(|e| e.ok()) {
             let path = entry.path();
             if path.is_file() {
@@ -437,6 +844,10 @@ fn get_unique_chars(src_dir: &Path) -> Result<(String, String)> {
                         .collect()
                 };
                 acc.extend(text.chars());
+                acc.extend(escapes::extract_escaped_chars(&text));
+                if path.extension().and_then(|e| e.to_str()) == Some("css") {
+                    acc.extend(escapes::extract_css_escapes(&text));
+                }
             }
             acc
         },
@@ -476,7 +887,40 @@ fn get_file_hash(path: &Path) -> String {
     }
 }
 
-fn get_font_key(path: &Path) -> Option<((String, String), bool, String, String)> {
+/// One `fvar` variation axis on a variable font, e.g. `wght` ranging 100-900.
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+struct VariableAxis {
+    tag: String,
+    min: f32,
+    default: f32,
+    max: f32,
+}
+
+#[derive(Clone)]
+struct FontMeta {
+    family: String,
+    style: String,
+    weight: u16,
+    width: u16,
+    slant: &'static str,
+    is_variable: bool,
+    variable_axes: Vec<VariableAxis>,
+}
+
+/// Decodes a `name` table record to a `String`, falling back to Mac OS Roman
+/// decoding for Macintosh-platform records that `ttf_parser` itself only
+/// decodes as UTF-16BE Windows/Unicode text.
+fn decode_name(name: &ttf_parser::name::Name) -> Option<String> {
+    if let Some(s) = name.to_string() {
+        return Some(s);
+    }
+    if name.platform_id == ttf_parser::PlatformId::Macintosh && name.encoding_id == 0 {
+        return Some(macroman::decode(name.name));
+    }
+    None
+}
+
+fn get_font_key(path: &Path) -> Option<FontMeta> {
     let file_name = path.file_name().unwrap().to_string_lossy();
     let bytes = match fs::read(path) {
         Ok(b) => b,
@@ -517,7 +961,7 @@ fn get_font_key(path: &Path) -> Option<((String, String), bool, String, String)>
                 .into_iter()
                 .find(|name| name.name_id == name_id::FAMILY)
         })
-        .and_then(|name| name.to_string())
+        .and_then(|name| decode_name(&name))
         .unwrap_or_else(|| "UnknownFamily".to_string());
     let style = names
         .into_iter()
@@ -527,13 +971,34 @@ fn get_font_key(path: &Path) -> Option<((String, String), bool, String, String)>
                 .into_iter()
                 .find(|name| name.name_id == name_id::SUBFAMILY)
         })
-        .and_then(|name| name.to_string())
+        .and_then(|name| decode_name(&name))
         .unwrap_or_else(|| "Regular".to_string());
     let is_variable = face.is_variable();
-    let display_style = if style != "Regular" {
-        style.clone()
-    } else {
-        String::new()
+    let weight = face.weight().to_number();
+    let width = face.width().to_number();
+    let slant = match face.style() {
+        ttf_parser::Style::Normal => "normal",
+        ttf_parser::Style::Italic => "italic",
+        ttf_parser::Style::Oblique => "oblique",
     };
-    Some(((family.clone(), style), is_variable, family, display_style))
+    let variable_axes = face
+        .variation_axes()
+        .into_iter()
+        .map(|axis| VariableAxis {
+            tag: axis.tag.to_string(),
+            min: axis.min_value,
+            default: axis.def_value,
+            max: axis.max_value,
+        })
+        .collect();
+
+    Some(FontMeta {
+        family,
+        style,
+        weight,
+        width,
+        slant,
+        is_variable,
+        variable_axes,
+    })
 }
\ No newline at end of file